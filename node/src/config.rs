@@ -0,0 +1,79 @@
+use crate::crypto::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("commit_chain_len must be at least 2 (it indexes the tracked ancestors), got {0}")]
+    InvalidCommitChainLen(usize),
+}
+
+pub type Stake = u32;
+pub type EpochNumber = u64;
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct Committee {
+    pub authorities: HashMap<PublicKey, Stake>,
+}
+
+impl Committee {
+    pub fn size(&self) -> usize {
+        self.authorities.len()
+    }
+
+    pub fn stake(&self, name: &PublicKey) -> Stake {
+        self.authorities.get(name).cloned().unwrap_or(0)
+    }
+
+    // The amount of stake a set of votes needs to carry to be treated as a quorum, i.e. more
+    // than two thirds of the total stake.
+    pub fn quorum_threshold(&self) -> Stake {
+        let total_stake: Stake = self.authorities.values().sum();
+        2 * total_stake / 3 + 1
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Parameters {
+    pub timeout_delay: u64,
+    pub sync_retry_delay: u64,
+    // The base of the exponential backoff applied to `timeout_delay` after consecutive
+    // round timeouts.
+    pub timeout_base: f64,
+    // The largest number of consecutive timeouts the backoff in `timeout_delay` accounts for;
+    // it caps the delay at `timeout_delay * timeout_base.powi(max_timeout_exponent)` instead of
+    // growing forever.
+    pub max_timeout_exponent: u64,
+    // How often, in milliseconds, the rebroadcast task resends our latest self-originated
+    // proposal and vote until the round they were made for is superseded.
+    pub rebroadcast_interval: u64,
+    // The number of consecutive ancestors `Core` tracks behind a proposal before committing the
+    // oldest one: 2 gives the 2-chain Jolteon rule, 3 the original 3-chain HotStuff rule.
+    pub commit_chain_len: usize,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            timeout_delay: 5_000,
+            sync_retry_delay: 10_000,
+            timeout_base: 1.5,
+            max_timeout_exponent: 6,
+            rebroadcast_interval: 1_000,
+            commit_chain_len: 2,
+        }
+    }
+}
+
+impl Parameters {
+    // Sanity-check values loaded from the node's configuration file. In particular
+    // `commit_chain_len` indexes directly into the ancestor list `Core` tracks, so anything
+    // below 2 would panic deep inside the commit path instead of failing fast here.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.commit_chain_len < 2 {
+            return Err(ConfigError::InvalidCommitChainLen(self.commit_chain_len));
+        }
+        Ok(())
+    }
+}
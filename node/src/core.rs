@@ -5,8 +5,9 @@ use crate::crypto::{Digest, PublicKey, SignatureService};
 use crate::error::{ConsensusError, ConsensusResult};
 use crate::leader::LeaderElector;
 use crate::mempool::Mempool;
-use crate::messages::{Block, GenericQC, Vote, QC, TC};
+use crate::messages::{timeout_digest, Block, GenericQC, Vote, QC, TC};
 use crate::network::NetMessage;
+use crate::rebroadcast::RebroadcastManager;
 use crate::store::Store;
 use crate::synchronizer::Synchronizer;
 use crate::timer::{TimerId, TimerManager};
@@ -14,7 +15,8 @@ use futures::future::FutureExt as _;
 use futures::select;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::cmp::max;
+use std::cmp::{max, min};
+use std::collections::HashMap;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 
 #[cfg(test)]
@@ -23,16 +25,27 @@ pub mod core_tests;
 
 pub type RoundNumber = u64;
 
+// A summary of how far a node has progressed, piggybacked on proposals and votes so that a
+// lagging peer can catch up from a single message instead of paying a full `SyncRequest`
+// round-trip for every missing round.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SyncInfo {
+    pub highest_qc: QC,
+    pub highest_tc: Option<TC>,
+    pub highest_commit_round: RoundNumber,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum CoreMessage {
-    Propose(Block),
-    Vote(Vote),
+    Propose(Block, SyncInfo),
+    Vote(Vote, SyncInfo),
     LoopBack(Block),
     SyncRequest(Digest, PublicKey),
 }
 
 pub struct Core {
     name: PublicKey,
+    epoch: u64,
     committee: Committee,
     parameters: Parameters,
     store: Store,
@@ -43,13 +56,20 @@ pub struct Core {
     timer_channel: Sender<TimerId>,
     network_channel: Sender<NetMessage>,
     commit_channel: Sender<Block>,
+    equivocation_channel: Sender<(Block, Block)>,
     round: RoundNumber,
     last_voted_round: RoundNumber,
     preferred_round: RoundNumber,
     highest_qc: QC,
+    highest_tc: Option<TC>,
+    highest_commit_round: RoundNumber,
     synchronizer: Synchronizer,
     aggregator: Aggregator,
     timer_manager: TimerManager,
+    rebroadcaster: RebroadcastManager,
+    consecutive_timeouts: u64,
+    seen_proposals: HashMap<RoundNumber, Block>,
+    reconfiguration_pending: bool,
 }
 
 impl Core {
@@ -64,9 +84,19 @@ impl Core {
         mempool: Mempool,
         network_channel: Sender<NetMessage>,
         commit_channel: Sender<Block>,
-    ) -> Sender<CoreMessage> {
+        equivocation_channel: Sender<(Block, Block)>,
+    ) -> (Sender<CoreMessage>, Sender<Committee>) {
+        parameters
+            .validate()
+            .expect("Invalid consensus parameters");
+
         let (tx_core, rx_core) = channel(1000);
 
+        // Make the reconfiguration channel. Once we commit a block carrying a reconfiguration
+        // payload, we wait on this channel for the application to tell us the next committee,
+        // so the application (not Core) controls membership changes.
+        let (tx_reconfig, rx_reconfig) = channel(10);
+
         // Make a timer manager instance allowing to schedule and cancel timers.
         // We communicate with the timer manager with a dedicated channel.
         let timer_manager = TimerManager::new().await;
@@ -88,11 +118,19 @@ impl Core {
         // of incoming votes and aggregates them into QCs.
         let aggregator = Aggregator::new(committee.clone());
 
+        // Make the rebroadcast manager. It keeps re-sending our latest self-originated
+        // proposal or vote until the round advances, so a lost packet recovers without
+        // waiting for the full round timeout.
+        let rebroadcaster =
+            RebroadcastManager::new(network_channel.clone(), parameters.rebroadcast_interval)
+                .await;
+
         // Run the core in a separate thread.
         let loopback_channel = tx_core.clone();
         tokio::spawn(async move {
             let mut core = Self {
                 name,
+                epoch: 0,
                 committee,
                 parameters,
                 store,
@@ -103,20 +141,28 @@ impl Core {
                 timer_channel: tx_timer,
                 network_channel,
                 commit_channel,
+                equivocation_channel,
                 round: 0,
                 last_voted_round: 0,
                 preferred_round: 0,
                 highest_qc: QC::genesis(),
+                highest_tc: None,
+                highest_commit_round: 0,
                 synchronizer,
                 aggregator,
                 timer_manager,
+                rebroadcaster,
+                consecutive_timeouts: 0,
+                seen_proposals: HashMap::new(),
+                reconfiguration_pending: false,
             };
-            core.run(rx_core, rx_timer).await;
+            core.run(rx_core, rx_timer, rx_reconfig).await;
         });
 
-        // Return sender channel. The network receiver will use it to
-        // send us new messages to process.
-        tx_core
+        // Return the sender channels. The network receiver uses `tx_core` to send us new
+        // messages to process, and the application uses `tx_reconfig` to hand us the next
+        // committee once it has decided on one.
+        (tx_core, tx_reconfig)
     }
 
     async fn store_block(&mut self, block: &Block) -> ConsensusResult<()> {
@@ -128,17 +174,42 @@ impl Core {
             .map_err(ConsensusError::from)
     }
 
+    // Compute the delay to arm the round timer with. The delay grows exponentially with the
+    // number of consecutive timeouts we have observed, so that a sustained network partition
+    // widens our patience instead of spinning through rounds at the same fixed pace. The
+    // multiplier is capped at `timeout_base.powi(max_timeout_exponent)` to avoid the delay
+    // growing unbounded.
+    fn timeout_delay(&self) -> u64 {
+        let exponent = min(self.consecutive_timeouts, self.parameters.max_timeout_exponent) as i32;
+        let multiplier = self.parameters.timeout_base.powi(exponent);
+        (self.parameters.timeout_delay as f64 * multiplier) as u64
+    }
+
     async fn schedule_timer(&mut self) {
         let timer_id = format!("core:{}", self.round);
         self.timer_manager
-            .schedule(
-                self.parameters.timeout_delay,
-                timer_id,
-                self.timer_channel.clone(),
-            )
+            .schedule(self.timeout_delay(), timer_id, self.timer_channel.clone())
             .await;
     }
 
+    // Build a summary of our current progress to piggyback on outgoing proposals and votes.
+    fn sync_info(&self) -> SyncInfo {
+        SyncInfo {
+            highest_qc: self.highest_qc.clone(),
+            highest_tc: self.highest_tc.clone(),
+            highest_commit_round: self.highest_commit_round,
+        }
+    }
+
+    // Remember `tc` as our highest known TC if it is newer than what we already have, so a
+    // node that only advanced through a timeout (rather than a QC) can still tell peers about
+    // it via `sync_info`.
+    fn update_highest_tc(&mut self, tc: &TC) {
+        if self.highest_tc.as_ref().map_or(true, |highest| tc.round > highest.round) {
+            self.highest_tc = Some(tc.clone());
+        }
+    }
+
     async fn make_block(
         &mut self,
         qc: QC,
@@ -146,6 +217,7 @@ impl Core {
         round: RoundNumber,
     ) -> ConsensusResult<()> {
         let block = Block::new(
+            self.epoch,
             qc,
             tc,
             self.name,
@@ -158,26 +230,116 @@ impl Core {
         if let Err(e) = self.loopback_channel.send(message).await {
             panic!("Core failed to loopback message to itself: {}", e);
         }
-        let message = NetMessage::Block(block);
+        let message = NetMessage::Block(block, self.sync_info());
+        self.rebroadcaster.set_block(round, message.clone()).await;
         if let Err(e) = self.network_channel.send(message).await {
             panic!("Core failed to send block to the network: {}", e);
         }
         Ok(())
     }
 
-    async fn handle_propose(&mut self, block: &Block) -> ConsensusResult<()> {
-        // Reject old blocks.
-        if block.round <= self.round {
-            return Ok(());
+    // Verify the `SyncInfo` piggybacked on a proposal or vote and, if it proves the sender is
+    // ahead of us, adopt its highest QC/TC and jump our round forward. This lets a single
+    // message from an up-to-date peer catch us up immediately, instead of discovering we are
+    // behind only once we are missing a block's ancestors and have to fall back to
+    // `SyncRequest`.
+    async fn handle_sync_info(&mut self, sync_info: &SyncInfo) -> ConsensusResult<()> {
+        if sync_info.highest_qc != QC::genesis() {
+            // A stale QC from a prior epoch can still carry a valid quorum of signatures under
+            // an unchanged or overlapping committee; without this check it could be smuggled
+            // inside a current-epoch message to force our round and timer to jump arbitrarily.
+            ensure!(
+                sync_info.highest_qc.epoch == self.epoch,
+                ConsensusError::WrongEpoch {
+                    digest: sync_info.highest_qc.hash.clone(),
+                    expected: self.epoch,
+                    received: sync_info.highest_qc.epoch,
+                }
+            );
+            sync_info.highest_qc.verify(&self.committee)?;
+        }
+        if let Some(tc) = &sync_info.highest_tc {
+            ensure!(
+                tc.epoch == self.epoch,
+                ConsensusError::WrongEpoch {
+                    digest: timeout_digest(tc.round),
+                    expected: self.epoch,
+                    received: tc.epoch,
+                }
+            );
+            tc.verify(&self.committee)?;
+            self.update_highest_tc(tc);
         }
 
-        // Check the block's round number is as expected. This prevents bad leaders
-        // from proposing blocks with very high round numbers which may cause overflows.
-        let ok = match block.tc {
-            Some(ref tc) => block.round == tc.round + 1,
-            None => block.round == block.qc.round + 1,
-        };
-        ensure!(ok, ConsensusError::MalformedBlock(block.digest()));
+        if sync_info.highest_qc.round > self.highest_qc.round {
+            self.highest_qc = sync_info.highest_qc.clone();
+        }
+
+        let highest_tc_round = sync_info.highest_tc.as_ref().map_or(0, |tc| tc.round);
+        let new_round = max(
+            self.round,
+            max(sync_info.highest_qc.round + 1, highest_tc_round + 1),
+        );
+        if new_round > self.round {
+            let timer_id = format!("core:{}", self.round);
+            self.timer_manager.cancel(timer_id).await;
+            self.round = new_round;
+            self.rebroadcaster.advance(self.round).await;
+            info!("Moved to round {} (via sync info)", self.round);
+            self.aggregator.cleanup(&self.round);
+            self.schedule_timer().await;
+        }
+        Ok(())
+    }
+
+    // Record the first validly-signed proposal we see per round, and turn a second,
+    // differently-digested one from the same (unique, per round) leader into cryptographic
+    // proof of equivocation for an external accountability layer. Must run on every proposal,
+    // including ones for rounds we have already passed, since those are exactly the ones
+    // `handle_propose` would otherwise silently drop.
+    async fn check_equivocation(&mut self, block: &Block) -> ConsensusResult<()> {
+        let digest = block.digest();
+        match self.seen_proposals.get(&block.round) {
+            None => {
+                self.seen_proposals.insert(block.round, block.clone());
+                Ok(())
+            }
+            Some(first) if first.digest() == digest => Ok(()),
+            Some(first) => {
+                let first = first.clone();
+                // Stashing the full block (not just its digest) means the proof can be forwarded
+                // even when the first proposal has not been stored yet, which is the common case
+                // for a Byzantine leader firing two conflicting blocks back-to-back.
+                if let Err(e) = self
+                    .equivocation_channel
+                    .send((first.clone(), block.clone()))
+                    .await
+                {
+                    warn!("Failed to forward equivocation proof: {}", e);
+                }
+                Err(ConsensusError::Equivocation {
+                    author: block.author,
+                    round: block.round,
+                    first: first.digest(),
+                    second: digest,
+                })
+            }
+        }
+    }
+
+    async fn handle_propose(&mut self, block: &Block, sync_info: &SyncInfo) -> ConsensusResult<()> {
+        // Reject messages from a different epoch outright: the committee, and thus the leader
+        // schedule and quorum size they were formed under, may no longer match ours.
+        ensure!(
+            block.epoch == self.epoch,
+            ConsensusError::WrongEpoch {
+                digest: block.digest(),
+                expected: self.epoch,
+                received: block.epoch,
+            }
+        );
+
+        self.handle_sync_info(sync_info).await?;
 
         // Ensure the block proposer is the right leader for the round.
         ensure!(
@@ -189,9 +351,29 @@ impl Core {
             }
         );
 
-        // Check the block is correctly signed.
+        // Check the block is correctly signed. This must happen before anything that trusts
+        // the block's contents, including equivocation detection below.
         block.signature.verify(&block.digest(), &block.author)?;
 
+        // Check the block's round number is as expected. This prevents bad leaders from
+        // proposing blocks with very high round numbers which may cause overflows, and must run
+        // before equivocation detection below so `seen_proposals` never records an unbounded,
+        // fabricated round number.
+        let ok = match block.tc {
+            Some(ref tc) => block.round == tc.round + 1,
+            None => block.round == block.qc.round + 1,
+        };
+        ensure!(ok, ConsensusError::MalformedBlock(block.digest()));
+
+        // Catch leader equivocation before the "reject old blocks" check below would otherwise
+        // silently drop a second, conflicting proposal for a round we already moved past.
+        self.check_equivocation(block).await?;
+
+        // Reject old blocks.
+        if block.round <= self.round {
+            return Ok(());
+        }
+
         // Check that the QC embedded in the block is valid.
         if block.qc != QC::genesis() {
             block.qc.verify(&self.committee)?;
@@ -200,6 +382,7 @@ impl Core {
         // Check the TC embedded in the block if any.
         if let Some(tc) = &block.tc {
             tc.verify(&self.committee)?;
+            self.update_highest_tc(tc);
         }
 
         // If all check pass, process the block.
@@ -213,15 +396,23 @@ impl Core {
             return Ok(());
         }
 
-        // Let's see if we have the last three ancestors of the block, that is:
-        //      b0 <- |qc0; b1| <- |qc1; b2| <- |qc2; block|
-        // If we don't, the synchronizer asks for them to other nodes. It will
-        // then ensure we process all three ancestors in the correct order, and
-        // finally make us resume processing this block.
-        let (b0, b1, b2) = match self.synchronizer.get_ancestors(block).await? {
+        // Let's see if we have the last `commit_chain_len` ancestors of the block, oldest
+        // first, e.g. for the default 2-chain Jolteon rule:
+        //      b1 <- |qc1; b2| <- |qc2; block|
+        // If we don't, the synchronizer sends a `SyncRequest` for the first one missing and we
+        // stop processing this block here; the synchronizer does not hand this block back to us
+        // once the ancestor arrives. `commit_chain_len` stays configurable so the old 3-chain
+        // HotStuff rule remains testable alongside the 2-chain one the crate is named after.
+        let ancestors = match self
+            .synchronizer
+            .get_ancestors(block, self.parameters.commit_chain_len)
+            .await?
+        {
             Some(ancestors) => ancestors,
             None => return Ok(()),
         };
+        let b1 = &ancestors[ancestors.len() - 2];
+        let b2 = &ancestors[ancestors.len() - 1];
 
         // If we have all ancestors we 'deliver' the block by adding it to store.
         // Delivering a block means we already processed all its ancestors.
@@ -237,11 +428,23 @@ impl Core {
             let timer_id = format!("core:{}", self.round);
             self.timer_manager.cancel(timer_id).await;
             self.round = possible_new_round;
+            self.rebroadcaster.advance(self.round).await;
             info!("Moved to round {}", self.round);
 
+            // A fresh QC (as opposed to a TC) means the round advanced through healthy
+            // progress rather than a timeout, so we can forget about past timeouts and
+            // go back to arming the timer with the base delay.
+            if block.tc.is_none() {
+                self.consecutive_timeouts = 0;
+            }
+
             // Cleanup the vote aggregator.
             self.aggregator.cleanup(&self.round);
 
+            // Bound the equivocation-detection map: we only need to remember proposals for
+            // rounds we have not yet passed.
+            self.seen_proposals.retain(|round, _| *round >= self.round);
+
             // Schedule a new timer for this round.
             self.schedule_timer().await;
         }
@@ -251,14 +454,29 @@ impl Core {
             self.highest_qc = block.qc.clone();
         }
 
-        // Check if the last three ancestors of the block form a 3-chain.
-        // If so, we commit b0.
-        let mut commit_rule = b0.round + 1 == b1.round;
-        commit_rule &= b1.round + 1 == b2.round;
-        commit_rule &= b2.round + 1 == block.round;
+        // Check if `ancestors` followed by `block` form an unbroken chain of consecutive
+        // rounds. If so, we commit the oldest tracked ancestor: with the default
+        // `commit_chain_len` of 2 that is a direct 2-chain (Jolteon's rule), with 3 it is the
+        // original HotStuff 3-chain.
+        let chain = ancestors.iter().chain(std::iter::once(block));
+        let commit_rule = chain
+            .clone()
+            .zip(chain.skip(1))
+            .all(|(parent, child)| parent.round + 1 == child.round);
         if commit_rule {
-            info!("Committed {:?}", b0);
-            if let Err(e) = self.commit_channel.send(b0.clone()).await {
+            let committed = &ancestors[0];
+            info!("Committed {:?}", committed);
+            self.highest_commit_round = max(self.highest_commit_round, committed.round);
+
+            // A committed block carrying a reconfiguration payload marks an epoch boundary.
+            // We keep running the current epoch until the application tells us, over the
+            // reconfiguration channel, what the next committee is.
+            if committed.payload.is_reconfiguration() {
+                info!("Committed reconfiguration block at epoch {}", self.epoch);
+                self.reconfiguration_pending = true;
+            }
+
+            if let Err(e) = self.commit_channel.send(committed.clone()).await {
                 warn!("Failed to send block through the commit channel: {}", e);
             }
         }
@@ -273,12 +491,13 @@ impl Core {
             let vote = Vote::new(&block, self.name, self.signature_service.clone()).await;
             let next_leader = self.leader_elector.get_leader(self.round + 1);
             if next_leader == self.name {
-                let message = CoreMessage::Vote(vote.clone());
+                let message = CoreMessage::Vote(vote.clone(), self.sync_info());
                 if let Err(e) = self.loopback_channel.send(message).await {
                     panic!("Core failed to loopback message to itself: {}", e);
                 }
             } else {
-                let message = NetMessage::Vote(vote, next_leader);
+                let message = NetMessage::Vote(vote, next_leader, self.sync_info());
+                self.rebroadcaster.set_vote(self.round, message.clone()).await;
                 if let Err(e) = self.network_channel.send(message).await {
                     panic!("Core failed to send vote to the network: {}", e);
                 }
@@ -292,7 +511,18 @@ impl Core {
         Ok(())
     }
 
-    async fn handle_vote(&mut self, vote: Vote) -> ConsensusResult<()> {
+    async fn handle_vote(&mut self, vote: Vote, sync_info: &SyncInfo) -> ConsensusResult<()> {
+        ensure!(
+            vote.epoch == self.epoch,
+            ConsensusError::WrongEpoch {
+                digest: vote.hash.clone(),
+                expected: self.epoch,
+                received: vote.epoch,
+            }
+        );
+
+        self.handle_sync_info(sync_info).await?;
+
         if vote.round < self.round {
             return Ok(());
         }
@@ -304,12 +534,15 @@ impl Core {
             if self.name == self.leader_elector.get_leader(next_round) {
                 let (qc, tc) = if vote.timeout() {
                     let tc = TC {
+                        epoch: self.epoch,
                         round: vote.round,
                         votes: quorum,
                     };
+                    self.update_highest_tc(&tc);
                     (self.highest_qc.clone(), Some(tc))
                 } else {
                     let qc = QC {
+                        epoch: self.epoch,
                         hash: vote.hash,
                         round: vote.round,
                         votes: quorum,
@@ -323,18 +556,26 @@ impl Core {
     }
 
     async fn make_timeout(&mut self) {
+        self.consecutive_timeouts += 1;
         self.round += 1;
+        self.rebroadcaster.advance(self.round).await;
         info!("Moved to round {}", self.round);
-        let timeout =
-            Vote::new_timeout(self.round, self.name, self.signature_service.clone()).await;
+        let timeout = Vote::new_timeout(
+            self.epoch,
+            self.round,
+            self.name,
+            self.signature_service.clone(),
+        )
+        .await;
         let next_leader = self.leader_elector.get_leader(self.round + 1);
         if next_leader == self.name {
-            let message = CoreMessage::Vote(timeout.clone());
+            let message = CoreMessage::Vote(timeout.clone(), self.sync_info());
             if let Err(e) = self.loopback_channel.send(message).await {
                 panic!("Core failed to loopback message to itself: {}", e);
             }
         } else {
-            let message = NetMessage::Vote(timeout, next_leader);
+            let message = NetMessage::Vote(timeout, next_leader, self.sync_info());
+            self.rebroadcaster.set_vote(self.round, message.clone()).await;
             if let Err(e) = self.network_channel.send(message).await {
                 panic!("Core failed to send vote to the network: {}", e);
             }
@@ -342,6 +583,43 @@ impl Core {
         self.schedule_timer().await;
     }
 
+    // Swap in the next committee once the application has decided on it, crossing the epoch
+    // boundary we opened when we committed a reconfiguration block. Everything that is scoped
+    // to the old committee is rebuilt or reset against the new epoch's genesis.
+    async fn reconfigure(&mut self, committee: Committee) {
+        if !self.reconfiguration_pending {
+            warn!("Ignoring unexpected committee update outside a reconfiguration");
+            return;
+        }
+
+        self.epoch += 1;
+        info!("Moving to epoch {}", self.epoch);
+        self.committee = committee;
+        self.leader_elector = LeaderElector::new(self.committee.clone());
+        self.aggregator = Aggregator::new(self.committee.clone());
+
+        let timer_id = format!("core:{}", self.round);
+        self.timer_manager.cancel(timer_id).await;
+        self.rebroadcaster.clear().await;
+
+        self.round = 0;
+        self.last_voted_round = 0;
+        self.preferred_round = 0;
+        self.highest_qc = QC::genesis();
+        self.highest_tc = None;
+        self.highest_commit_round = 0;
+        self.consecutive_timeouts = 0;
+        self.seen_proposals.clear();
+        self.reconfiguration_pending = false;
+
+        self.schedule_timer().await;
+        if self.name == self.leader_elector.get_leader(1) {
+            self.make_block(self.highest_qc.clone(), None, 1)
+                .await
+                .expect("Failed to send the first block of the new epoch");
+        }
+    }
+
     async fn handle_sync_request(
         &mut self,
         digest: Digest,
@@ -357,7 +635,12 @@ impl Core {
         Ok(())
     }
 
-    async fn run(&mut self, mut rx_core: Receiver<CoreMessage>, mut rx_timer: Receiver<TimerId>) {
+    async fn run(
+        &mut self,
+        mut rx_core: Receiver<CoreMessage>,
+        mut rx_timer: Receiver<TimerId>,
+        mut rx_reconfig: Receiver<Committee>,
+    ) {
         // Upon booting, send the very first block (if we are the leader).
         // and schedule a timer in case we don't hear from the leader.
         self.schedule_timer().await;
@@ -374,8 +657,8 @@ impl Core {
                     if let Some(message) = message {
                         debug!("Received {:?}", message);
                         let result = match message {
-                            CoreMessage::Propose(block) => self.handle_propose(&block).await,
-                            CoreMessage::Vote(vote) => self.handle_vote(vote).await,
+                            CoreMessage::Propose(block, sync_info) => self.handle_propose(&block, &sync_info).await,
+                            CoreMessage::Vote(vote, sync_info) => self.handle_vote(vote, &sync_info).await,
                             CoreMessage::LoopBack(block) => self.process_block(&block).await,
                             CoreMessage::SyncRequest(digest, sender) => self.handle_sync_request(digest, sender).await
                         };
@@ -392,6 +675,11 @@ impl Core {
                         warn!("Timing out for round {}!", self.round);
                         self.make_timeout().await
                     }
+                },
+                committee = rx_reconfig.recv().fuse() => {
+                    if let Some(committee) = committee {
+                        self.reconfigure(committee).await
+                    }
                 }
             }
         }
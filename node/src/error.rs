@@ -0,0 +1,71 @@
+use crate::config::EpochNumber;
+use crate::core::RoundNumber;
+use crate::crypto::{Digest, PublicKey};
+use thiserror::Error;
+
+#[macro_export]
+macro_rules! bail {
+    ($e:expr) => {
+        return Err($e)
+    };
+}
+
+#[macro_export(local_inner_macros)]
+macro_rules! ensure {
+    ($cond:expr, $e:expr) => {
+        if !($cond) {
+            bail!($e);
+        }
+    };
+}
+
+pub type ConsensusResult<T> = Result<T, ConsensusError>;
+
+#[derive(Error, Debug)]
+pub enum ConsensusError {
+    #[error("Network error: {0}")]
+    NetworkError(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] Box<bincode::ErrorKind>),
+
+    #[error("Store error: {0}")]
+    StoreError(#[from] crate::store::StoreError),
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Received more than one vote from {0}")]
+    AuthorityReuse(PublicKey),
+
+    #[error("Received vote from unknown authority {0}")]
+    UnknownAuthority(PublicKey),
+
+    #[error("Received QC/TC without a quorum")]
+    QCRequiresQuorum,
+
+    #[error("Malformed block {0}")]
+    MalformedBlock(Digest),
+
+    #[error("Received block {digest} from wrong leader {leader} for round {round}")]
+    WrongLeader {
+        digest: Digest,
+        leader: PublicKey,
+        round: RoundNumber,
+    },
+
+    #[error("Leader {author} equivocated at round {round}: {first} vs {second}")]
+    Equivocation {
+        author: PublicKey,
+        round: RoundNumber,
+        first: Digest,
+        second: Digest,
+    },
+
+    #[error("Message {digest} is for epoch {received}, expected {expected}")]
+    WrongEpoch {
+        digest: Digest,
+        expected: EpochNumber,
+        received: EpochNumber,
+    },
+}
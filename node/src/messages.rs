@@ -0,0 +1,217 @@
+use crate::config::{Committee, EpochNumber};
+use crate::core::RoundNumber;
+use crate::crypto::{Digest, Hash, PublicKey, Signature, SignatureService};
+use crate::error::{ConsensusError, ConsensusResult};
+use crate::mempool::Payload;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Block {
+    pub epoch: EpochNumber,
+    pub qc: QC,
+    pub tc: Option<TC>,
+    pub author: PublicKey,
+    pub round: RoundNumber,
+    pub payload: Payload,
+    pub signature: Signature,
+}
+
+impl Block {
+    pub async fn new(
+        epoch: EpochNumber,
+        qc: QC,
+        tc: Option<TC>,
+        author: PublicKey,
+        round: RoundNumber,
+        payload: Payload,
+        mut signature_service: SignatureService,
+    ) -> Self {
+        let block = Self {
+            epoch,
+            qc,
+            tc,
+            author,
+            round,
+            payload,
+            signature: Signature::default(),
+        };
+        let signature = signature_service.request_signature(block.digest()).await;
+        Self { signature, ..block }
+    }
+}
+
+impl Hash for Block {
+    fn digest(&self) -> Digest {
+        let mut hasher = crate::crypto::Hasher::new();
+        hasher.update(self.epoch.to_le_bytes());
+        hasher.update(self.qc.hash.as_ref());
+        hasher.update(self.round.to_le_bytes());
+        hasher.update(self.author.as_ref());
+        hasher.update(self.payload.digest().as_ref());
+        hasher.finalize()
+    }
+}
+
+impl std::fmt::Debug for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: B(author {}, round {}, qc {:?})",
+            self.digest(),
+            self.author,
+            self.round,
+            self.qc
+        )
+    }
+}
+
+// Behaviour shared by `QC` and `TC`: a quantity that a proposal (or the round timer) may trail,
+// and that can be checked to actually carry a quorum of the committee's stake. Implemented by
+// both so `Core` can verify and compare either one without matching on which it got.
+pub trait GenericQC {
+    fn round(&self) -> RoundNumber;
+    fn verify(&self, committee: &Committee) -> ConsensusResult<()>;
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct QC {
+    pub epoch: EpochNumber,
+    pub hash: Digest,
+    pub round: RoundNumber,
+    pub votes: Vec<(PublicKey, Signature)>,
+}
+
+impl QC {
+    pub fn genesis() -> Self {
+        Self::default()
+    }
+}
+
+impl GenericQC for QC {
+    fn round(&self) -> RoundNumber {
+        self.round
+    }
+
+    fn verify(&self, committee: &Committee) -> ConsensusResult<()> {
+        verify_votes(self.epoch, &self.hash, self.round, &self.votes, committee)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct TC {
+    pub epoch: EpochNumber,
+    pub round: RoundNumber,
+    pub votes: Vec<(PublicKey, Signature)>,
+}
+
+impl GenericQC for TC {
+    fn round(&self) -> RoundNumber {
+        self.round
+    }
+
+    fn verify(&self, committee: &Committee) -> ConsensusResult<()> {
+        let digest = timeout_digest(self.round);
+        verify_votes(self.epoch, &digest, self.round, &self.votes, committee)
+    }
+}
+
+// Checks that `votes` carries a quorum of the committee's stake and that each signature is a
+// genuine vote for `(epoch, hash, round)` — i.e. it reconstructs the exact digest
+// `Hash for Vote` produced for that voter (see below) rather than checking against `hash` alone,
+// since that's what `Vote::new`/`Vote::new_timeout` actually sign.
+fn verify_votes(
+    epoch: EpochNumber,
+    hash: &Digest,
+    round: RoundNumber,
+    votes: &[(PublicKey, Signature)],
+    committee: &Committee,
+) -> ConsensusResult<()> {
+    let mut used = std::collections::HashSet::new();
+    let mut weight = 0;
+    for (author, signature) in votes {
+        ensure!(used.insert(*author), ConsensusError::AuthorityReuse(*author));
+        let stake = committee.stake(author);
+        ensure!(stake > 0, ConsensusError::UnknownAuthority(*author));
+        weight += stake;
+        let digest = vote_digest(epoch, hash, round, author);
+        signature.verify(&digest, author)?;
+    }
+    ensure!(
+        weight >= committee.quorum_threshold(),
+        ConsensusError::QCRequiresQuorum
+    );
+    Ok(())
+}
+
+pub(crate) fn timeout_digest(round: RoundNumber) -> Digest {
+    let mut hasher = crate::crypto::Hasher::new();
+    hasher.update(round.to_le_bytes());
+    hasher.update(b"timeout");
+    hasher.finalize()
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Vote {
+    pub epoch: EpochNumber,
+    pub hash: Digest,
+    pub round: RoundNumber,
+    pub author: PublicKey,
+    pub signature: Signature,
+}
+
+impl Vote {
+    pub async fn new(
+        block: &Block,
+        author: PublicKey,
+        mut signature_service: SignatureService,
+    ) -> Self {
+        let vote = Self {
+            epoch: block.epoch,
+            hash: block.digest(),
+            round: block.round,
+            author,
+            signature: Signature::default(),
+        };
+        let signature = signature_service.request_signature(vote.digest()).await;
+        Self { signature, ..vote }
+    }
+
+    pub async fn new_timeout(
+        epoch: EpochNumber,
+        round: RoundNumber,
+        author: PublicKey,
+        mut signature_service: SignatureService,
+    ) -> Self {
+        let vote = Self {
+            epoch,
+            hash: timeout_digest(round),
+            round,
+            author,
+            signature: Signature::default(),
+        };
+        let signature = signature_service.request_signature(vote.digest()).await;
+        Self { signature, ..vote }
+    }
+
+    pub fn timeout(&self) -> bool {
+        self.hash == timeout_digest(self.round)
+    }
+}
+
+// Factored out of `Hash for Vote` so `verify_votes` can reconstruct the exact digest a voter
+// signed from the `(author, signature)` pairs carried by a `QC`/`TC`, without having a `Vote` for
+// each one lying around.
+fn vote_digest(epoch: EpochNumber, hash: &Digest, round: RoundNumber, author: &PublicKey) -> Digest {
+    let mut hasher = crate::crypto::Hasher::new();
+    hasher.update(epoch.to_le_bytes());
+    hasher.update(hash.as_ref());
+    hasher.update(round.to_le_bytes());
+    hasher.update(author.as_ref());
+    hasher.finalize()
+}
+
+impl Hash for Vote {
+    fn digest(&self) -> Digest {
+        vote_digest(self.epoch, &self.hash, self.round, &self.author)
+    }
+}
@@ -0,0 +1,14 @@
+use crate::core::SyncInfo;
+use crate::crypto::{Digest, PublicKey};
+use crate::messages::{Block, Vote};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum NetMessage {
+    Block(Block, SyncInfo),
+    Vote(Vote, PublicKey, SyncInfo),
+    // (digest, requester, target): `requester` is who to send the `SyncReply` back to, `target`
+    // is the peer the network layer should actually deliver this request to.
+    SyncRequest(Digest, PublicKey, PublicKey),
+    SyncReply(Block, PublicKey),
+}
@@ -0,0 +1,111 @@
+use crate::core::RoundNumber;
+use crate::network::NetMessage;
+use tokio::sync::mpsc::{channel, Sender};
+use tokio::time::{interval, Duration};
+
+// Commands accepted by the rebroadcast background task.
+enum RebroadcastCommand {
+    SetBlock(RoundNumber, NetMessage),
+    SetVote(RoundNumber, NetMessage),
+    Advance(RoundNumber),
+    Clear,
+}
+
+// Keeps re-sending our most recent self-originated proposal and vote to the network until the
+// round they were made for is superseded, so a single dropped packet costs at most
+// `rebroadcast_interval` instead of a full round timeout. Sibling to `TimerManager`: a small
+// background task Core talks to over a channel, rather than a synchronous collaborator.
+//
+// The block and the vote for a round are tracked in two independent slots, each tagged with
+// the round it was made for. `advance` only drops a slot once the round has moved past it:
+// Core routinely reaches a new round by processing the very message it just set (e.g. its own
+// proposal looped back to itself), so an unconditional "round changed, wipe everything" would
+// delete that message before the ticker ever got a chance to resend it.
+#[derive(Clone)]
+pub struct RebroadcastManager {
+    tx: Sender<RebroadcastCommand>,
+}
+
+impl RebroadcastManager {
+    pub async fn new(network_channel: Sender<NetMessage>, rebroadcast_interval: u64) -> Self {
+        let (tx, mut rx) = channel(100);
+        tokio::spawn(async move {
+            let mut ticker = interval(Duration::from_millis(rebroadcast_interval));
+            let mut block: Option<(RoundNumber, NetMessage)> = None;
+            let mut vote: Option<(RoundNumber, NetMessage)> = None;
+            loop {
+                tokio::select! {
+                    command = rx.recv() => match command {
+                        Some(RebroadcastCommand::SetBlock(round, message)) => {
+                            block = Some((round, message));
+                        }
+                        Some(RebroadcastCommand::SetVote(round, message)) => {
+                            vote = Some((round, message));
+                        }
+                        Some(RebroadcastCommand::Advance(round)) => {
+                            if block.as_ref().map_or(false, |(r, _)| *r < round) {
+                                block = None;
+                            }
+                            if vote.as_ref().map_or(false, |(r, _)| *r < round) {
+                                vote = None;
+                            }
+                        }
+                        Some(RebroadcastCommand::Clear) => {
+                            block = None;
+                            vote = None;
+                        }
+                        None => return,
+                    },
+                    _ = ticker.tick() => {
+                        for slot in [&block, &vote] {
+                            if let Some((_, message)) = slot {
+                                if let Err(e) = network_channel.send(message.clone()).await {
+                                    panic!("Failed to rebroadcast message to the network: {}", e);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        });
+        Self { tx }
+    }
+
+    // Remember `message` as the proposal to keep rebroadcasting for `round`.
+    pub async fn set_block(&mut self, round: RoundNumber, message: NetMessage) {
+        if let Err(e) = self
+            .tx
+            .send(RebroadcastCommand::SetBlock(round, message))
+            .await
+        {
+            panic!("Failed to send command to rebroadcast manager: {}", e);
+        }
+    }
+
+    // Remember `message` as the vote to keep rebroadcasting for `round`.
+    pub async fn set_vote(&mut self, round: RoundNumber, message: NetMessage) {
+        if let Err(e) = self
+            .tx
+            .send(RebroadcastCommand::SetVote(round, message))
+            .await
+        {
+            panic!("Failed to send command to rebroadcast manager: {}", e);
+        }
+    }
+
+    // Drop any tracked message whose round is now behind `round`. Called whenever we learn the
+    // round advanced, so a message only disappears once it is genuinely stale.
+    pub async fn advance(&mut self, round: RoundNumber) {
+        if let Err(e) = self.tx.send(RebroadcastCommand::Advance(round)).await {
+            panic!("Failed to send command to rebroadcast manager: {}", e);
+        }
+    }
+
+    // Unconditionally drop both slots. Used when round numbers from before and after are no
+    // longer comparable, i.e. across an epoch change.
+    pub async fn clear(&mut self) {
+        if let Err(e) = self.tx.send(RebroadcastCommand::Clear).await {
+            panic!("Failed to send command to rebroadcast manager: {}", e);
+        }
+    }
+}
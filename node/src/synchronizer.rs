@@ -0,0 +1,88 @@
+use crate::core::CoreMessage;
+use crate::crypto::{Digest, PublicKey};
+use crate::error::ConsensusResult;
+use crate::messages::Block;
+use crate::network::NetMessage;
+use crate::store::Store;
+use crate::timer::TimerManager;
+use tokio::sync::mpsc::Sender;
+
+// Looks up the ancestors of a block in the store and asks other nodes for the first one that is
+// missing. Runs as a collaborator of `Core` rather than a separate task: `get_ancestors` reads
+// the store, sends at most one `SyncRequest`, and returns without waiting for a reply. It does
+// not retry requests, and it does not hand the block back to `Core` once the ancestor arrives.
+#[derive(Clone)]
+pub struct Synchronizer {
+    name: PublicKey,
+    store: Store,
+    network_channel: Sender<NetMessage>,
+    core_channel: Sender<CoreMessage>,
+    timer_manager: TimerManager,
+    sync_retry_delay: u64,
+}
+
+impl Synchronizer {
+    pub async fn new(
+        name: PublicKey,
+        store: Store,
+        network_channel: Sender<NetMessage>,
+        core_channel: Sender<CoreMessage>,
+        timer_manager: TimerManager,
+        sync_retry_delay: u64,
+    ) -> Self {
+        Self {
+            name,
+            store,
+            network_channel,
+            core_channel,
+            timer_manager,
+            sync_retry_delay,
+        }
+    }
+
+    async fn get_block(&mut self, digest: Digest) -> ConsensusResult<Option<Block>> {
+        match self.store.read(digest.to_vec()).await? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Returns the block's last `chain_len` ancestors, oldest first, once all of them are in
+    // store. If any is missing, asks the author of the block that references it for it and
+    // returns `None`; the synchronizer does not send `Core` a `LoopBack` for the block later.
+    // `chain_len` is configurable (see `Parameters::commit_chain_len`) so the same synchronizer
+    // serves both the 2-chain Jolteon commit rule and the original 3-chain HotStuff one.
+    pub async fn get_ancestors(
+        &mut self,
+        block: &Block,
+        chain_len: usize,
+    ) -> ConsensusResult<Option<Vec<Block>>> {
+        let mut ancestors = Vec::with_capacity(chain_len);
+        let mut parent_hash = block.qc.hash.clone();
+        let mut requester = block.author;
+        for _ in 0..chain_len {
+            let ancestor = match self.get_block(parent_hash.clone()).await? {
+                Some(ancestor) => ancestor,
+                None => {
+                    self.request(parent_hash, requester).await;
+                    return Ok(None);
+                }
+            };
+            parent_hash = ancestor.qc.hash.clone();
+            requester = ancestor.author;
+            ancestors.push(ancestor);
+        }
+        ancestors.reverse();
+        Ok(Some(ancestors))
+    }
+
+    // Ask `sender` (the author of the block we were trying to complete) for the block we are
+    // missing. We don't wait for the reply here, and nothing in the synchronizer loops the
+    // block back to `Core` when it arrives.
+    async fn request(&mut self, digest: Digest, sender: PublicKey) {
+        let message = NetMessage::SyncRequest(digest, self.name, sender);
+        if let Err(e) = self.network_channel.send(message).await {
+            panic!("Failed to send sync request to the network: {}", e);
+        }
+    }
+}
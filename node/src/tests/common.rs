@@ -0,0 +1,63 @@
+use crate::config::{Committee, Parameters};
+use crate::crypto::{generate_keypair, PublicKey, SecretKey, SignatureService};
+use crate::messages::{Block, Vote, QC};
+use rand::rngs::StdRng;
+use rand::SeedableRng as _;
+
+// A single authority with all the stake, so any one of its votes already forms a quorum. Plenty
+// for unit-testing Core's internal logic without the bookkeeping of a multi-node committee.
+pub fn keys() -> (PublicKey, SecretKey) {
+    let mut rng = StdRng::from_seed([0; 32]);
+    generate_keypair(&mut rng)
+}
+
+pub fn committee(name: PublicKey) -> Committee {
+    Committee {
+        authorities: vec![(name, 1)].into_iter().collect(),
+    }
+}
+
+pub fn parameters() -> Parameters {
+    Parameters {
+        commit_chain_len: 2,
+        ..Parameters::default()
+    }
+}
+
+pub fn signature_service(secret: SecretKey) -> SignatureService {
+    SignatureService::new(secret)
+}
+
+// Builds a chain of `len` blocks on top of `QC::genesis()`, signed by `leader`, each one
+// certifying its parent. Returned oldest first, so the caller can feed them to `process_block`
+// in order.
+pub async fn chain(
+    epoch: u64,
+    leader: PublicKey,
+    mut signature_service: SignatureService,
+    len: u64,
+) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut qc = QC::genesis();
+    for round in 1..=len {
+        let block = Block::new(
+            epoch,
+            qc,
+            None,
+            leader,
+            round,
+            Default::default(),
+            signature_service.clone(),
+        )
+        .await;
+        let vote = Vote::new(&block, leader, signature_service.clone()).await;
+        qc = QC {
+            epoch,
+            hash: vote.hash.clone(),
+            round: vote.round,
+            votes: vec![(leader, vote.signature.clone())],
+        };
+        blocks.push(block);
+    }
+    blocks
+}
@@ -0,0 +1,278 @@
+use super::*;
+use crate::mempool::Mempool;
+use crate::store::Store;
+use crate::timer::TimerManager;
+use common::{chain, committee, keys, parameters, signature_service};
+use std::collections::HashMap;
+use tempfile::tempdir;
+use tokio::sync::mpsc::channel;
+
+#[path = "common.rs"]
+mod common;
+
+// Builds a bare `Core` directly (rather than through `Core::make`) so tests can call its
+// private methods and inspect its state without spinning up the background task.
+async fn make_core(name: PublicKey, store: Store) -> Core {
+    let committee = committee(name);
+    let parameters = parameters();
+    let leader_elector = LeaderElector::new(committee.clone());
+    let mempool = Mempool::new();
+    let timer_manager = TimerManager::new().await;
+    let (tx_core, rx_core) = channel(1000);
+    let (tx_timer, rx_timer) = channel(100);
+    let (tx_network, rx_network) = channel(1000);
+    let (tx_commit, rx_commit) = channel(1000);
+    let (tx_equivocation, rx_equivocation) = channel(10);
+    // Keep the receiving ends open for the rest of the test: `Core` panics when it cannot loop a
+    // message back to itself or hand one to the network.
+    std::mem::forget((rx_core, rx_timer, rx_network, rx_commit, rx_equivocation));
+    let synchronizer = Synchronizer::new(
+        name,
+        store.clone(),
+        tx_network.clone(),
+        tx_core.clone(),
+        timer_manager.clone(),
+        parameters.sync_retry_delay,
+    )
+    .await;
+    let aggregator = Aggregator::new(committee.clone());
+    let rebroadcaster =
+        RebroadcastManager::new(tx_network.clone(), parameters.rebroadcast_interval).await;
+
+    Core {
+        name,
+        epoch: 0,
+        committee,
+        parameters,
+        store,
+        signature_service: signature_service(keys().1),
+        leader_elector,
+        mempool,
+        loopback_channel: tx_core,
+        timer_channel: tx_timer,
+        network_channel: tx_network,
+        commit_channel: tx_commit,
+        equivocation_channel: tx_equivocation,
+        round: 0,
+        last_voted_round: 0,
+        preferred_round: 0,
+        highest_qc: QC::genesis(),
+        highest_tc: None,
+        highest_commit_round: 0,
+        synchronizer,
+        aggregator,
+        timer_manager,
+        rebroadcaster,
+        consecutive_timeouts: 0,
+        seen_proposals: HashMap::new(),
+        reconfiguration_pending: false,
+    }
+}
+
+fn temp_store() -> Store {
+    Store::new(tempdir().unwrap().path().to_str().unwrap()).unwrap()
+}
+
+// A `SyncInfo` carrying a QC from well ahead of us should jump our round forward, reaching the
+// QC catch-up path the feature exists for (`L57-hit/Jolteon#chunk0-2`).
+#[tokio::test]
+async fn sync_info_advances_round() {
+    let (name, secret) = keys();
+    let mut core = make_core(name, temp_store()).await;
+
+    let blocks = chain(0, name, signature_service(secret), 3).await;
+    let ahead = SyncInfo {
+        highest_qc: blocks.last().unwrap().qc.clone(),
+        highest_tc: None,
+        highest_commit_round: 0,
+    };
+
+    core.handle_sync_info(&ahead).await.unwrap();
+
+    assert_eq!(core.round, ahead.highest_qc.round + 1);
+    assert_eq!(core.highest_qc.round, ahead.highest_qc.round);
+}
+
+// A second, differently-digested proposal from the same leader for a round we already saw must
+// be reported as an `Equivocation` error naming that leader and round
+// (`L57-hit/Jolteon#chunk0-4`).
+#[tokio::test]
+async fn conflicting_proposals_are_equivocation() {
+    let (name, secret) = keys();
+    let mut core = make_core(name, temp_store()).await;
+    let signature_service = signature_service(secret);
+
+    let first = Block::new(
+        0,
+        QC::genesis(),
+        None,
+        name,
+        1,
+        Default::default(),
+        signature_service.clone(),
+    )
+    .await;
+    core.store_block(&first).await.unwrap();
+    core.check_equivocation(&first).await.unwrap();
+
+    // Same epoch and round as `first`, but built on a different parent QC, as a conflicting
+    // leader equivocating would send.
+    let mut forked_hasher = crate::crypto::Hasher::new();
+    forked_hasher.update(b"fork");
+    let forked_qc = QC {
+        hash: forked_hasher.finalize(),
+        ..QC::genesis()
+    };
+    let second = Block::new(
+        0,
+        forked_qc,
+        None,
+        name,
+        1,
+        Default::default(),
+        signature_service.clone(),
+    )
+    .await;
+    assert_ne!(first.digest(), second.digest());
+
+    let error = core.check_equivocation(&second).await.unwrap_err();
+    match error {
+        ConsensusError::Equivocation { author, round, .. } => {
+            assert_eq!(author, name);
+            assert_eq!(round, 1);
+        }
+        _ => panic!("expected an Equivocation error"),
+    }
+}
+
+// `timeout_delay` backs off exponentially with consecutive timeouts, capped at
+// `timeout_base.powi(max_timeout_exponent)` so a sustained partition widens our patience instead
+// of growing the delay without bound (`L57-hit/Jolteon#chunk0-1`).
+#[tokio::test]
+async fn timeout_delay_backs_off_and_caps() {
+    let (name, _) = keys();
+    let mut core = make_core(name, temp_store()).await;
+
+    assert_eq!(core.timeout_delay(), core.parameters.timeout_delay);
+
+    core.consecutive_timeouts = 1;
+    let expected = (core.parameters.timeout_delay as f64 * core.parameters.timeout_base) as u64;
+    assert_eq!(core.timeout_delay(), expected);
+
+    core.consecutive_timeouts = core.parameters.max_timeout_exponent;
+    let capped = (core.parameters.timeout_delay as f64
+        * core
+            .parameters
+            .timeout_base
+            .powi(core.parameters.max_timeout_exponent as i32)) as u64;
+    assert_eq!(core.timeout_delay(), capped);
+
+    // Further consecutive timeouts must not grow the delay past the cap.
+    core.consecutive_timeouts = core.parameters.max_timeout_exponent + 10;
+    assert_eq!(core.timeout_delay(), capped);
+}
+
+// `RebroadcastManager` keeps resending the most recent message tracked for a round until told the
+// round has been superseded, at which point it stops (`L57-hit/Jolteon#chunk0-3`).
+#[tokio::test]
+async fn rebroadcast_manager_resends_until_advanced() {
+    let (name, secret) = keys();
+    let block = Block::new(
+        0,
+        QC::genesis(),
+        None,
+        name,
+        1,
+        Default::default(),
+        signature_service(secret),
+    )
+    .await;
+    let sync_info = SyncInfo {
+        highest_qc: QC::genesis(),
+        highest_tc: None,
+        highest_commit_round: 0,
+    };
+    let message = NetMessage::Block(block, sync_info);
+
+    let (tx_network, mut rx_network) = channel(10);
+    let mut rebroadcaster = RebroadcastManager::new(tx_network, 10).await;
+    rebroadcaster.set_block(1, message.clone()).await;
+
+    // Wait long enough for the ticker to resend the tracked block at least once.
+    tokio::time::sleep(std::time::Duration::from_millis(35)).await;
+    assert!(
+        rx_network.try_recv().is_ok(),
+        "expected the ticker to resend the tracked block"
+    );
+
+    // Drain any further resends, then tell the manager we moved past round 1.
+    while rx_network.try_recv().is_ok() {}
+    rebroadcaster.advance(2).await;
+
+    // The slot was dropped, so the next tick should resend nothing.
+    tokio::time::sleep(std::time::Duration::from_millis(35)).await;
+    assert!(rx_network.try_recv().is_err());
+}
+
+// Committing a reconfiguration block marks the epoch boundary; once the application supplies the
+// next committee, `reconfigure` must bump the epoch and reset all per-epoch state
+// (`L57-hit/Jolteon#chunk0-5`).
+#[tokio::test]
+async fn reconfigure_bumps_epoch_and_resets_state() {
+    let (name, _) = keys();
+    let mut core = make_core(name, temp_store()).await;
+    core.round = 7;
+    core.last_voted_round = 7;
+    core.reconfiguration_pending = true;
+
+    let next_committee = committee(name);
+    core.reconfigure(next_committee).await;
+
+    assert_eq!(core.epoch, 1);
+    assert_eq!(core.round, 0);
+    assert_eq!(core.last_voted_round, 0);
+    assert!(!core.reconfiguration_pending);
+    assert_eq!(core.highest_qc, QC::genesis());
+}
+
+// With `commit_chain_len` set to 2, processing the third block of the chain commits its
+// grandparent (round 1) directly off a 2-chain; with 3, the same proposal instead commits the
+// genesis ancestor one round further back (round 0), so `highest_commit_round` stays put
+// (`L57-hit/Jolteon#chunk0-6`).
+#[tokio::test]
+async fn commit_chain_len_controls_the_commit_rule() {
+    let (name, secret) = keys();
+    let blocks = chain(0, name, signature_service(secret), 3).await;
+
+    for chain_len in [2usize, 3usize] {
+        let mut core = make_core(name, temp_store()).await;
+        core.parameters.commit_chain_len = chain_len;
+
+        // Seed the store with every ancestor `process_block` will need to walk back to, short
+        // of real history: a synthetic round-0 block at the genesis QC's digest so a
+        // `commit_chain_len` of 3 can resolve one level further back than the three real blocks
+        // go, plus the two real blocks preceding the one we hand to `process_block`.
+        let genesis_block = Block {
+            round: 0,
+            ..blocks[0].clone()
+        };
+        core.store
+            .write(
+                QC::genesis().hash.to_vec(),
+                bincode::serialize(&genesis_block).unwrap(),
+            )
+            .await
+            .unwrap();
+        for block in &blocks[..2] {
+            core.store_block(block).await.unwrap();
+        }
+
+        core.process_block(&blocks[2]).await.unwrap();
+
+        if chain_len == 2 {
+            assert_eq!(core.highest_commit_round, blocks[0].round);
+        } else {
+            assert_eq!(core.highest_commit_round, 0);
+        }
+    }
+}